@@ -10,19 +10,84 @@ pub enum Field {
     /// float
     Float(f64),
     /// unsigned integer
-    UnsignedInt(u32),
+    UnsignedInt(u64),
+    /// signed integer
+    SignedInt(i64),
+    /// boolean
+    Boolean(bool),
 }
 
-fn encode_events(events: Vec<Metric>, namespace: &str) -> Vec<String> {
+/// Timestamp precision used when emitting InfluxDB line protocol.
+///
+/// InfluxDB accepts timestamps at second, millisecond, microsecond, or
+/// nanosecond precision; the sink defaults to nanoseconds to match the
+/// wire format InfluxDB itself uses internally.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Precision {
+    Seconds,
+    Millis,
+    Micros,
+    Nanos,
+}
+
+impl Default for Precision {
+    fn default() -> Self {
+        Precision::Nanos
+    }
+}
+
+impl Precision {
+    fn new_timestamp(self, nanos: i64) -> i64 {
+        match self {
+            Precision::Seconds => nanos / 1_000_000_000,
+            Precision::Millis => nanos / 1_000_000,
+            Precision::Micros => nanos / 1_000,
+            Precision::Nanos => nanos,
+        }
+    }
+}
+
+/// A float value that is guaranteed to be finite (not `NaN` or `+/-inf`).
+///
+/// InfluxDB line protocol has no representation for these values, so any
+/// field built from a non-finite float must be dropped rather than encoded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FiniteF64(f64);
+
+impl FiniteF64 {
+    fn new(value: f64) -> Option<Self> {
+        if value.is_finite() {
+            Some(FiniteF64(value))
+        } else {
+            None
+        }
+    }
+
+    fn as_f64(self) -> f64 {
+        self.0
+    }
+}
+
+/// Default quantiles emitted for `Distribution` metrics when the sink isn't
+/// configured with its own set.
+const DEFAULT_QUANTILES: [f64; 5] = [0.5, 0.75, 0.9, 0.95, 0.99];
+
+fn encode_events(
+    events: Vec<Metric>,
+    namespace: &str,
+    precision: Precision,
+    quantiles: &[f64],
+) -> Vec<String> {
     events
         .into_iter()
         .filter_map(|event| {
             let fullname = encode_namespace(namespace, &event.name);
-            let ts = encode_timestamp(event.timestamp);
+            let ts = event.timestamp;
             let tags = event.tags.clone();
+            let unit = event.unit.clone();
             match event.value {
                 MetricValue::Counter { value } => {
-                    let fields = to_fields(value);
+                    let fields = with_unit(to_fields(value), unit);
 
                     Some(vec![influx_line_protocol(
                         fullname,
@@ -30,10 +95,11 @@ fn encode_events(events: Vec<Metric>, namespace: &str) -> Vec<String> {
                         tags,
                         Some(fields),
                         ts,
+                        precision,
                     )])
                 }
                 MetricValue::Gauge { value } => {
-                    let fields = to_fields(value);
+                    let fields = with_unit(to_fields(value), unit);
 
                     Some(vec![influx_line_protocol(
                         fullname,
@@ -41,10 +107,11 @@ fn encode_events(events: Vec<Metric>, namespace: &str) -> Vec<String> {
                         tags,
                         Some(fields),
                         ts,
+                        precision,
                     )])
                 }
                 MetricValue::Set { values } => {
-                    let fields = to_fields(values.len() as f64);
+                    let fields = with_unit(to_fields(values.len() as f64), unit);
 
                     Some(vec![influx_line_protocol(
                         fullname,
@@ -52,6 +119,7 @@ fn encode_events(events: Vec<Metric>, namespace: &str) -> Vec<String> {
                         tags,
                         Some(fields),
                         ts,
+                        precision,
                     )])
                 }
                 MetricValue::AggregatedHistogram {
@@ -63,10 +131,16 @@ fn encode_events(events: Vec<Metric>, namespace: &str) -> Vec<String> {
                     let mut fields: HashMap<String, Field> = buckets
                         .iter()
                         .zip(counts.iter())
-                        .map(|pair| (format!("bucket_{}", pair.0), Field::UnsignedInt(*pair.1)))
+                        .map(|pair| {
+                            (
+                                format!("bucket_{}", pair.0),
+                                Field::UnsignedInt(u64::from(*pair.1)),
+                            )
+                        })
                         .collect();
-                    fields.insert("count".to_owned(), Field::UnsignedInt(count));
+                    fields.insert("count".to_owned(), Field::UnsignedInt(u64::from(count)));
                     fields.insert("sum".to_owned(), Field::Float(sum));
+                    let fields = with_unit(fields, unit);
 
                     Some(vec![influx_line_protocol(
                         fullname,
@@ -74,6 +148,7 @@ fn encode_events(events: Vec<Metric>, namespace: &str) -> Vec<String> {
                         tags,
                         Some(fields),
                         ts,
+                        precision,
                     )])
                 }
                 MetricValue::AggregatedSummary {
@@ -87,8 +162,9 @@ fn encode_events(events: Vec<Metric>, namespace: &str) -> Vec<String> {
                         .zip(values.iter())
                         .map(|pair| (format!("quantile_{}", pair.0), Field::Float(*pair.1)))
                         .collect();
-                    fields.insert("count".to_owned(), Field::UnsignedInt(count));
+                    fields.insert("count".to_owned(), Field::UnsignedInt(u64::from(count)));
                     fields.insert("sum".to_owned(), Field::Float(sum));
+                    let fields = with_unit(fields, unit);
 
                     Some(vec![influx_line_protocol(
                         fullname,
@@ -96,19 +172,22 @@ fn encode_events(events: Vec<Metric>, namespace: &str) -> Vec<String> {
                         tags,
                         Some(fields),
                         ts,
+                        precision,
                     )])
                 }
                 MetricValue::Distribution {
                     values,
                     sample_rates,
                 } => {
-                    let fields = encode_distribution(&values, &sample_rates);
+                    let fields = encode_distribution(&values, &sample_rates, quantiles)
+                        .map(|fields| with_unit(fields, unit));
                     Some(vec![influx_line_protocol(
                         fullname,
                         "distribution",
                         tags,
                         fields,
                         ts,
+                        precision,
                     )])
                 }
             }
@@ -118,63 +197,76 @@ fn encode_events(events: Vec<Metric>, namespace: &str) -> Vec<String> {
         .collect()
 }
 
-fn encode_distribution(values: &[f64], counts: &[u32]) -> Option<HashMap<String, Field>> {
+fn encode_distribution(
+    values: &[f64],
+    counts: &[u32],
+    quantiles: &[f64],
+) -> Option<HashMap<String, Field>> {
     if values.len() != counts.len() {
         return None;
     }
 
-    let mut samples = Vec::new();
-    for (v, c) in values.iter().zip(counts.iter()) {
-        for _ in 0..*c {
-            samples.push(*v);
-        }
-    }
+    let mut pairs: Vec<(f64, u64)> = values
+        .iter()
+        .zip(counts.iter())
+        .filter(|(_, &c)| c > 0)
+        .map(|(v, &c)| (*v, u64::from(c)))
+        .collect();
 
-    if samples.is_empty() {
+    if pairs.is_empty() {
         return None;
     }
 
-    if samples.len() == 1 {
-        let val = samples[0];
-        return Some(
-            vec![
-                ("min".to_owned(), Field::Float(val)),
-                ("max".to_owned(), Field::Float(val)),
-                ("median".to_owned(), Field::Float(val)),
-                ("avg".to_owned(), Field::Float(val)),
-                ("sum".to_owned(), Field::Float(val)),
-                ("count".to_owned(), Field::Float(1.0)),
-                ("quantile_0.95".to_owned(), Field::Float(val)),
-            ]
-            .into_iter()
-            .collect(),
-        );
-    }
-
-    samples.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    let total: u64 = pairs.iter().map(|(_, c)| c).sum();
 
-    let length = samples.len() as f64;
-    let min = samples.first().unwrap();
-    let max = samples.last().unwrap();
+    if total == 1 {
+        let val = pairs[0].0;
+        let mut fields: HashMap<String, Field> = vec![
+            ("min".to_owned(), Field::Float(val)),
+            ("max".to_owned(), Field::Float(val)),
+            ("avg".to_owned(), Field::Float(val)),
+            ("sum".to_owned(), Field::Float(val)),
+            ("count".to_owned(), Field::UnsignedInt(1)),
+        ]
+        .into_iter()
+        .collect();
+        for &q in quantiles {
+            fields.insert(format!("quantile_{}", q), Field::Float(val));
+        }
+        return Some(fields);
+    }
 
-    let p50 = samples[(0.50 * length - 1.0).round() as usize];
-    let p95 = samples[(0.95 * length - 1.0).round() as usize];
+    pairs.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
 
-    let sum = samples.iter().sum();
-    let avg = sum / length;
+    let min = pairs.first().unwrap().0;
+    let max = pairs.last().unwrap().0;
+    let sum: f64 = pairs.iter().map(|(v, c)| v * (*c as f64)).sum();
+    let avg = sum / total as f64;
 
-    let fields: HashMap<String, Field> = vec![
-        ("min".to_owned(), Field::Float(*min)),
-        ("max".to_owned(), Field::Float(*max)),
-        ("median".to_owned(), Field::Float(p50)),
+    let mut fields: HashMap<String, Field> = vec![
+        ("min".to_owned(), Field::Float(min)),
+        ("max".to_owned(), Field::Float(max)),
         ("avg".to_owned(), Field::Float(avg)),
         ("sum".to_owned(), Field::Float(sum)),
-        ("count".to_owned(), Field::Float(length)),
-        ("quantile_0.95".to_owned(), Field::Float(p95)),
+        ("count".to_owned(), Field::UnsignedInt(total)),
     ]
     .into_iter()
     .collect();
 
+    for &q in quantiles {
+        let rank = ((q * total as f64).ceil() as u64).max(1);
+        let mut cumulative = 0;
+        let mut value = max;
+        for &(v, c) in &pairs {
+            cumulative += c;
+            if cumulative >= rank {
+                value = v;
+                break;
+            }
+        }
+        fields.insert(format!("quantile_{}", q), Field::Float(value));
+    }
+
     Some(fields)
 }
 
@@ -183,7 +275,8 @@ fn influx_line_protocol(
     metric_type: &str,
     tags: Option<HashMap<String, String>>,
     fields: Option<HashMap<String, Field>>,
-    timestamp: i64,
+    timestamp: Option<DateTime<Utc>>,
+    precision: Precision,
 ) -> String {
     let mut line_protocol = vec![encode_key(measurement)];
 
@@ -201,7 +294,7 @@ fn influx_line_protocol(
     line_protocol.push(format!(" {}", encoded_fields));
 
     // Timestamp
-    line_protocol.push(format!(" {}", timestamp));
+    line_protocol.push(format!(" {}", encode_timestamp(timestamp, precision)));
 
     line_protocol.join("")
 }
@@ -241,35 +334,36 @@ fn encode_fields(fields: HashMap<String, Field>) -> String {
         // sort by key
         .iter()
         .collect::<BTreeMap<_, _>>()
-        // map to key=value
+        // map to key=value, dropping fields that can't be represented in line protocol
         .iter()
-        .map(|pair| {
+        .filter_map(|pair| {
             let key = encode_key(pair.0.to_string());
             let value = match pair.1 {
                 Field::String(s) => {
                     let escaped = s.replace("\\", "\\\\").replace("\"", "\\\"");
-                    format!("\"{}\"", escaped)
+                    Some(format!("\"{}\"", escaped))
                 }
-                Field::Float(f) => f.to_string(),
-                Field::UnsignedInt(i) => format!("{}i", i.to_string()),
-            };
+                Field::Float(f) => FiniteF64::new(*f).map(|v| v.as_f64().to_string()),
+                Field::UnsignedInt(i) => Some(format!("{}u", i)),
+                Field::SignedInt(i) => Some(format!("{}i", i)),
+                Field::Boolean(b) => Some(b.to_string()),
+            }?;
             if !key.is_empty() && !value.is_empty() {
-                format!("{}={}", key, value)
+                Some(format!("{}={}", key, value))
             } else {
-                "".to_string()
+                None
             }
         })
-        .filter(|field_value| !field_value.is_empty())
         .collect::<Vec<String>>();
 
     encoded.join(",")
 }
 
-fn encode_timestamp(timestamp: Option<DateTime<Utc>>) -> i64 {
+fn encode_timestamp(timestamp: Option<DateTime<Utc>>, precision: Precision) -> i64 {
     if let Some(ts) = timestamp {
-        ts.timestamp_nanos()
+        precision.new_timestamp(ts.timestamp_nanos())
     } else {
-        encode_timestamp(Some(Utc::now()))
+        encode_timestamp(Some(Utc::now()), precision)
     }
 }
 
@@ -288,6 +382,26 @@ fn to_fields(value: f64) -> HashMap<String, Field> {
     fields
 }
 
+/// Whether `fields` contains at least one field that will actually survive
+/// `encode_fields` (i.e. not just a non-finite float).
+fn has_encodable_field(fields: &HashMap<String, Field>) -> bool {
+    fields.values().any(|field| match field {
+        Field::Float(f) => FiniteF64::new(*f).is_some(),
+        Field::String(_) | Field::UnsignedInt(_) | Field::SignedInt(_) | Field::Boolean(_) => true,
+    })
+}
+
+fn with_unit(mut fields: HashMap<String, Field>, unit: Option<String>) -> HashMap<String, Field> {
+    if let Some(unit) = unit {
+        // Don't let `unit` alone make an otherwise-empty metric look non-empty;
+        // a metric with no valid data fields must still be dropped.
+        if has_encodable_field(&fields) {
+            fields.insert("unit".to_owned(), Field::String(unit));
+        }
+    }
+    fields
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -311,8 +425,31 @@ mod tests {
     #[test]
     fn test_encode_timestamp() {
         let start = Utc::now().timestamp_nanos();
-        assert_eq!(encode_timestamp(Some(ts())), 1542182950000000011);
-        assert!(encode_timestamp(None) >= start)
+        assert_eq!(
+            encode_timestamp(Some(ts()), Precision::Nanos),
+            1542182950000000011
+        );
+        assert!(encode_timestamp(None, Precision::Nanos) >= start)
+    }
+
+    #[test]
+    fn test_encode_timestamp_precision() {
+        assert_eq!(
+            encode_timestamp(Some(ts()), Precision::Seconds),
+            1542182950
+        );
+        assert_eq!(
+            encode_timestamp(Some(ts()), Precision::Millis),
+            1542182950000
+        );
+        assert_eq!(
+            encode_timestamp(Some(ts()), Precision::Micros),
+            1542182950000000
+        );
+        assert_eq!(
+            encode_timestamp(Some(ts()), Precision::Nanos),
+            1542182950000000011
+        );
     }
 
     #[test]
@@ -380,6 +517,40 @@ mod tests {
         assert_eq!(encode_fields(fields), "escape\\ key=10,field_float=123.45,field_string=\"string value\",field_string_escape=\"string\\\\val\\\"ue\"");
     }
 
+    #[test]
+    fn test_encode_fields_integer_and_boolean() {
+        let fields = vec![
+            ("field_unsigned".to_owned(), Field::UnsignedInt(u64::MAX)),
+            ("field_signed".to_owned(), Field::SignedInt(-123)),
+            ("field_bool_true".to_owned(), Field::Boolean(true)),
+            ("field_bool_false".to_owned(), Field::Boolean(false)),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            encode_fields(fields),
+            "field_bool_false=false,field_bool_true=true,field_signed=-123i,field_unsigned=18446744073709551615u"
+        );
+    }
+
+    #[test]
+    fn test_encode_fields_drops_non_finite_floats() {
+        let fields = vec![
+            ("field_float".to_owned(), Field::Float(123.45)),
+            ("field_nan".to_owned(), Field::Float(std::f64::NAN)),
+            ("field_inf".to_owned(), Field::Float(std::f64::INFINITY)),
+            (
+                "field_neg_inf".to_owned(),
+                Field::Float(std::f64::NEG_INFINITY),
+            ),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(encode_fields(fields), "field_float=123.45");
+    }
+
     #[test]
     fn encode_counter() {
         let events = vec![
@@ -388,6 +559,7 @@ mod tests {
                 timestamp: Some(ts()),
                 tags: None,
                 kind: MetricKind::Incremental,
+                unit: None,
                 value: MetricValue::Counter { value: 1.5 },
             },
             Metric {
@@ -395,17 +567,124 @@ mod tests {
                 timestamp: Some(ts()),
                 tags: Some(tags()),
                 kind: MetricKind::Incremental,
+                unit: None,
                 value: MetricValue::Counter { value: 1.0 },
             },
         ];
 
-        let line_protocols = encode_events(events, "ns");
+        let line_protocols = encode_events(events, "ns", Precision::Nanos, &DEFAULT_QUANTILES);
         assert_eq!(
             line_protocols,
             vec!["ns.total,metric_type=counter value=1.5 1542182950000000011", "ns.check,metric_type=counter,normal_tag=value,true_tag=true value=1 1542182950000000011", ]
         );
     }
 
+    #[test]
+    fn encode_counter_with_unit() {
+        let events = vec![Metric {
+            name: "total".into(),
+            timestamp: Some(ts()),
+            tags: None,
+            kind: MetricKind::Incremental,
+            unit: Some("bytes".to_owned()),
+            value: MetricValue::Counter { value: 1.5 },
+        }];
+
+        let line_protocols = encode_events(events, "ns", Precision::Nanos, &DEFAULT_QUANTILES);
+        assert_eq!(
+            line_protocols,
+            vec!["ns.total,metric_type=counter unit=\"bytes\",value=1.5 1542182950000000011"]
+        );
+    }
+
+    #[test]
+    fn encode_counter_seconds_precision() {
+        let events = vec![Metric {
+            name: "total".into(),
+            timestamp: Some(ts()),
+            tags: None,
+            kind: MetricKind::Incremental,
+            unit: None,
+            value: MetricValue::Counter { value: 1.5 },
+        }];
+
+        let line_protocols = encode_events(events, "ns", Precision::Seconds, &DEFAULT_QUANTILES);
+        assert_eq!(
+            line_protocols,
+            vec!["ns.total,metric_type=counter value=1.5 1542182950"]
+        );
+    }
+
+    #[test]
+    fn encode_counter_non_finite() {
+        let events = vec![Metric {
+            name: "total".into(),
+            timestamp: Some(ts()),
+            tags: None,
+            kind: MetricKind::Incremental,
+            unit: None,
+            value: MetricValue::Counter {
+                value: std::f64::NAN,
+            },
+        }];
+
+        let line_protocols = encode_events(events, "ns", Precision::Nanos, &DEFAULT_QUANTILES);
+        assert_eq!(line_protocols.len(), 0);
+    }
+
+    #[test]
+    fn encode_counter_non_finite_with_unit() {
+        let events = vec![Metric {
+            name: "total".into(),
+            timestamp: Some(ts()),
+            tags: None,
+            kind: MetricKind::Incremental,
+            unit: Some("bytes".to_owned()),
+            value: MetricValue::Counter {
+                value: std::f64::NAN,
+            },
+        }];
+
+        let line_protocols = encode_events(events, "ns", Precision::Nanos, &DEFAULT_QUANTILES);
+        assert_eq!(line_protocols.len(), 0);
+    }
+
+    #[test]
+    fn test_has_encodable_field_mixed_map() {
+        let all_non_finite: HashMap<String, Field> =
+            vec![("sum".to_owned(), Field::Float(std::f64::NAN))]
+                .into_iter()
+                .collect();
+        assert!(!has_encodable_field(&all_non_finite));
+
+        // A histogram/summary-shaped map always has an `UnsignedInt` count
+        // field alongside its floats, so it's "encodable" even when every
+        // float in it (e.g. `sum`) is NaN/inf.
+        let mixed: HashMap<String, Field> = vec![
+            ("count".to_owned(), Field::UnsignedInt(6)),
+            ("sum".to_owned(), Field::Float(std::f64::NAN)),
+        ]
+        .into_iter()
+        .collect();
+        assert!(has_encodable_field(&mixed));
+    }
+
+    #[test]
+    fn test_with_unit_mixed_map() {
+        let mixed: HashMap<String, Field> = vec![
+            ("count".to_owned(), Field::UnsignedInt(6)),
+            ("sum".to_owned(), Field::Float(std::f64::NAN)),
+        ]
+        .into_iter()
+        .collect();
+
+        let fields = with_unit(mixed, Some("bytes".to_owned()));
+        assert_eq!(
+            encode_fields(fields),
+            "count=6u,unit=\"bytes\""
+        );
+    }
+
     #[test]
     fn encode_gauge() {
         let events = vec![Metric {
@@ -413,10 +692,11 @@ mod tests {
             timestamp: Some(ts()),
             tags: Some(tags()),
             kind: MetricKind::Incremental,
+            unit: None,
             value: MetricValue::Gauge { value: -1.5 },
         }];
 
-        let line_protocols = encode_events(events, "ns");
+        let line_protocols = encode_events(events, "ns", Precision::Nanos, &DEFAULT_QUANTILES);
         assert_eq!(
             line_protocols,
             vec!["ns.meter,metric_type=gauge,normal_tag=value,true_tag=true value=-1.5 1542182950000000011", ]
@@ -430,12 +710,13 @@ mod tests {
             timestamp: Some(ts()),
             tags: Some(tags()),
             kind: MetricKind::Incremental,
+            unit: None,
             value: MetricValue::Set {
                 values: vec!["alice".into(), "bob".into()].into_iter().collect(),
             },
         }];
 
-        let line_protocols = encode_events(events, "ns");
+        let line_protocols = encode_events(events, "ns", Precision::Nanos, &DEFAULT_QUANTILES);
         assert_eq!(
             line_protocols,
             vec!["ns.users,metric_type=set,normal_tag=value,true_tag=true value=2 1542182950000000011", ]
@@ -449,6 +730,7 @@ mod tests {
             timestamp: Some(ts()),
             tags: Some(tags()),
             kind: MetricKind::Absolute,
+            unit: None,
             value: MetricValue::AggregatedHistogram {
                 buckets: vec![1.0, 2.1, 3.0],
                 counts: vec![1, 2, 3],
@@ -457,10 +739,33 @@ mod tests {
             },
         }];
 
-        let line_protocols = encode_events(events, "ns");
+        let line_protocols = encode_events(events, "ns", Precision::Nanos, &DEFAULT_QUANTILES);
+        assert_eq!(
+            line_protocols,
+            vec!["ns.requests,metric_type=histogram,normal_tag=value,true_tag=true bucket_1=1u,bucket_2.1=2u,bucket_3=3u,count=6u,sum=12.5 1542182950000000011", ]
+        );
+    }
+
+    #[test]
+    fn encode_histogram_non_finite_sum_with_unit() {
+        let events = vec![Metric {
+            name: "requests".to_owned(),
+            timestamp: Some(ts()),
+            tags: None,
+            kind: MetricKind::Absolute,
+            unit: Some("bytes".to_owned()),
+            value: MetricValue::AggregatedHistogram {
+                buckets: vec![1.0, 2.1, 3.0],
+                counts: vec![1, 2, 3],
+                count: 6,
+                sum: std::f64::NAN,
+            },
+        }];
+
+        let line_protocols = encode_events(events, "ns", Precision::Nanos, &DEFAULT_QUANTILES);
         assert_eq!(
             line_protocols,
-            vec!["ns.requests,metric_type=histogram,normal_tag=value,true_tag=true bucket_1=1i,bucket_2.1=2i,bucket_3=3i,count=6i,sum=12.5 1542182950000000011", ]
+            vec!["ns.requests,metric_type=histogram bucket_1=1u,bucket_2.1=2u,bucket_3=3u,count=6u,unit=\"bytes\" 1542182950000000011"]
         );
     }
 
@@ -471,6 +776,7 @@ mod tests {
             timestamp: Some(ts()),
             tags: Some(tags()),
             kind: MetricKind::Absolute,
+            unit: None,
             value: MetricValue::AggregatedSummary {
                 quantiles: vec![0.01, 0.5, 0.99],
                 values: vec![1.5, 2.0, 3.0],
@@ -479,10 +785,10 @@ mod tests {
             },
         }];
 
-        let line_protocols = encode_events(events, "ns");
+        let line_protocols = encode_events(events, "ns", Precision::Nanos, &DEFAULT_QUANTILES);
         assert_eq!(
             line_protocols,
-            vec!["ns.requests_sum,metric_type=summary,normal_tag=value,true_tag=true count=6i,quantile_0.01=1.5,quantile_0.5=2,quantile_0.99=3,sum=12 1542182950000000011", ]
+            vec!["ns.requests_sum,metric_type=summary,normal_tag=value,true_tag=true count=6u,quantile_0.01=1.5,quantile_0.5=2,quantile_0.99=3,sum=12 1542182950000000011", ]
         );
     }
 
@@ -494,6 +800,7 @@ mod tests {
                 timestamp: Some(ts()),
                 tags: Some(tags()),
                 kind: MetricKind::Incremental,
+                unit: None,
                 value: MetricValue::Distribution {
                     values: vec![1.0, 2.0, 3.0],
                     sample_rates: vec![3, 3, 2],
@@ -504,6 +811,7 @@ mod tests {
                 timestamp: Some(ts()),
                 tags: None,
                 kind: MetricKind::Incremental,
+                unit: None,
                 value: MetricValue::Distribution {
                     values: (0..20).into_iter().map(f64::from).collect::<Vec<_>>(),
                     sample_rates: vec![1; 20],
@@ -514,6 +822,7 @@ mod tests {
                 timestamp: Some(ts()),
                 tags: None,
                 kind: MetricKind::Incremental,
+                unit: None,
                 value: MetricValue::Distribution {
                     values: (1..5).into_iter().map(f64::from).collect::<Vec<_>>(),
                     sample_rates: (1..5).into_iter().collect::<Vec<_>>(),
@@ -521,13 +830,13 @@ mod tests {
             },
         ];
 
-        let line_protocols = encode_events(events, "ns");
+        let line_protocols = encode_events(events, "ns", Precision::Nanos, &DEFAULT_QUANTILES);
         assert_eq!(
             line_protocols,
             vec![
-                "ns.requests,metric_type=distribution,normal_tag=value,true_tag=true avg=1.875,count=8,max=3,median=2,min=1,quantile_0.95=3,sum=15 1542182950000000011",
-                "ns.dense_stats,metric_type=distribution avg=9.5,count=20,max=19,median=9,min=0,quantile_0.95=18,sum=190 1542182950000000011",
-                "ns.sparse_stats,metric_type=distribution avg=3,count=10,max=4,median=3,min=1,quantile_0.95=4,sum=30 1542182950000000011",
+                "ns.requests,metric_type=distribution,normal_tag=value,true_tag=true avg=1.875,count=8u,max=3,min=1,quantile_0.5=2,quantile_0.75=2,quantile_0.9=3,quantile_0.95=3,quantile_0.99=3,sum=15 1542182950000000011",
+                "ns.dense_stats,metric_type=distribution avg=9.5,count=20u,max=19,min=0,quantile_0.5=9,quantile_0.75=14,quantile_0.9=17,quantile_0.95=18,quantile_0.99=19,sum=190 1542182950000000011",
+                "ns.sparse_stats,metric_type=distribution avg=3,count=10u,max=4,min=1,quantile_0.5=3,quantile_0.75=4,quantile_0.9=4,quantile_0.95=4,quantile_0.99=4,sum=30 1542182950000000011",
             ]
         );
     }
@@ -539,13 +848,14 @@ mod tests {
             timestamp: Some(ts()),
             tags: Some(tags()),
             kind: MetricKind::Incremental,
+            unit: None,
             value: MetricValue::Distribution {
                 values: vec![],
                 sample_rates: vec![],
             },
         }];
 
-        let line_protocols = encode_events(events, "ns");
+        let line_protocols = encode_events(events, "ns", Precision::Nanos, &DEFAULT_QUANTILES);
         assert_eq!(line_protocols.len(), 0);
     }
 
@@ -556,13 +866,14 @@ mod tests {
             timestamp: Some(ts()),
             tags: Some(tags()),
             kind: MetricKind::Incremental,
+            unit: None,
             value: MetricValue::Distribution {
                 values: vec![1.0, 2.0],
                 sample_rates: vec![0, 0],
             },
         }];
 
-        let line_protocols = encode_events(events, "ns");
+        let line_protocols = encode_events(events, "ns", Precision::Nanos, &DEFAULT_QUANTILES);
         assert_eq!(line_protocols.len(), 0);
     }
 
@@ -573,13 +884,77 @@ mod tests {
             timestamp: Some(ts()),
             tags: Some(tags()),
             kind: MetricKind::Incremental,
+            unit: None,
             value: MetricValue::Distribution {
                 values: vec![1.0],
                 sample_rates: vec![1, 2, 3],
             },
         }];
 
-        let line_protocols = encode_events(events, "ns");
+        let line_protocols = encode_events(events, "ns", Precision::Nanos, &DEFAULT_QUANTILES);
         assert_eq!(line_protocols.len(), 0);
     }
+
+    #[test]
+    fn encode_distribution_single_sample() {
+        let events = vec![Metric {
+            name: "requests".into(),
+            timestamp: Some(ts()),
+            tags: None,
+            kind: MetricKind::Incremental,
+            unit: None,
+            value: MetricValue::Distribution {
+                values: vec![4.2],
+                sample_rates: vec![1],
+            },
+        }];
+
+        let line_protocols = encode_events(events, "ns", Precision::Nanos, &DEFAULT_QUANTILES);
+        assert_eq!(
+            line_protocols,
+            vec!["ns.requests,metric_type=distribution avg=4.2,count=1u,max=4.2,min=4.2,quantile_0.5=4.2,quantile_0.75=4.2,quantile_0.9=4.2,quantile_0.95=4.2,quantile_0.99=4.2,sum=4.2 1542182950000000011"]
+        );
+    }
+
+    #[test]
+    fn encode_distribution_configurable_quantiles() {
+        let events = vec![Metric {
+            name: "requests".into(),
+            timestamp: Some(ts()),
+            tags: None,
+            kind: MetricKind::Incremental,
+            unit: None,
+            value: MetricValue::Distribution {
+                values: vec![1.0, 2.0, 3.0, 4.0],
+                sample_rates: vec![1, 1, 1, 1],
+            },
+        }];
+
+        let line_protocols = encode_events(events, "ns", Precision::Nanos, &[0.5, 1.0]);
+        assert_eq!(
+            line_protocols,
+            vec!["ns.requests,metric_type=distribution avg=2.5,count=4u,max=4,min=1,quantile_0.5=2,quantile_1=4,sum=10 1542182950000000011"]
+        );
+    }
+
+    #[test]
+    fn encode_distribution_large_counts_without_expanding_samples() {
+        let events = vec![Metric {
+            name: "requests".into(),
+            timestamp: Some(ts()),
+            tags: None,
+            kind: MetricKind::Incremental,
+            unit: None,
+            value: MetricValue::Distribution {
+                values: vec![1.0, 2.0],
+                sample_rates: vec![1_000_000, 1],
+            },
+        }];
+
+        let line_protocols = encode_events(events, "ns", Precision::Nanos, &DEFAULT_QUANTILES);
+        assert_eq!(
+            line_protocols,
+            vec!["ns.requests,metric_type=distribution avg=1.000000999999,count=1000001u,max=2,min=1,quantile_0.5=1,quantile_0.75=1,quantile_0.9=1,quantile_0.95=1,quantile_0.99=1,sum=1000002 1542182950000000011"]
+        );
+    }
 }